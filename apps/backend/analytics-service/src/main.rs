@@ -1,17 +1,35 @@
 use axum::{
-    extract::{Path, State},
-    http::StatusCode,
-    response::Json,
+    body::Bytes,
+    extract::{FromRequest, FromRequestParts, Path, Query, Request, State},
+    http::{header::HeaderMap, request::Parts, StatusCode},
+    response::{
+        sse::{Event, KeepAlive, Sse},
+        Json,
+    },
     routing::{get, post},
     Router,
 };
+use futures::stream::{Stream, StreamExt};
+use async_trait::async_trait;
+use hmac::{Hmac, Mac};
+use jsonwebtoken::{decode, DecodingKey, Validation};
+use prometheus::{
+    CounterVec, Encoder, Gauge, Histogram, HistogramOpts, Opts, Registry, TextEncoder,
+};
+use sha2::Sha256;
 use serde::{Deserialize, Serialize};
-use std::{collections::HashMap, sync::Arc};
-use tokio::sync::RwLock;
+use std::{collections::HashMap, convert::Infallible, sync::Arc, time::Duration};
+use tokio::sync::{broadcast, RwLock};
+use tokio_stream::wrappers::BroadcastStream;
 use tower_http::cors::{Any, CorsLayer};
 use tracing::{info, Level};
 use uuid::Uuid;
 
+/// Buffer size for the live event broadcast channel. A subscriber that falls
+/// more than this many events behind misses the oldest of them; the stream
+/// skips the resulting lag error and keeps delivering newer events.
+const EVENT_CHANNEL_CAPACITY: usize = 1024;
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 struct AnalyticsEvent {
     id: Uuid,
@@ -35,7 +53,490 @@ struct EventStats {
     recent_events: Vec<AnalyticsEvent>,
 }
 
-type AppState = Arc<RwLock<Vec<AnalyticsEvent>>>;
+/// Default number of events returned by `/events` when no `limit` is given.
+const DEFAULT_EVENTS_LIMIT: usize = 100;
+
+/// Hard cap on the `limit` query parameter to keep responses bounded.
+const MAX_EVENTS_LIMIT: usize = 1000;
+
+/// Query parameters accepted by the `/events` listing endpoint.
+#[derive(Debug, Deserialize)]
+struct EventsQuery {
+    /// Exact-match filter on `event_type`.
+    event_type: Option<String>,
+    /// Exact-match filter on `user_id`.
+    user_id: Option<String>,
+    /// Lower bound (inclusive) on `timestamp`, as an RFC3339 string.
+    since: Option<chrono::DateTime<chrono::Utc>>,
+    /// Upper bound (inclusive) on `timestamp`, as an RFC3339 string.
+    until: Option<chrono::DateTime<chrono::Utc>>,
+    /// Maximum number of events to return (defaults to 100, capped at 1000).
+    limit: Option<usize>,
+    /// Number of matching events to skip, for pagination.
+    offset: Option<usize>,
+}
+
+impl EventsQuery {
+    /// Resolved page size, defaulted and capped.
+    fn limit(&self) -> usize {
+        self.limit.unwrap_or(DEFAULT_EVENTS_LIMIT).min(MAX_EVENTS_LIMIT)
+    }
+
+    /// Resolved pagination offset.
+    fn offset(&self) -> usize {
+        self.offset.unwrap_or(0)
+    }
+}
+
+/// Query parameters accepted by the `/events/stream` SSE endpoint.
+#[derive(Debug, Deserialize)]
+struct StreamQuery {
+    /// When set, only events whose `event_type` matches are forwarded.
+    event_type: Option<String>,
+}
+
+/// Prometheus collectors exposed on `/metrics`.
+#[derive(Clone)]
+struct Metrics {
+    registry: Registry,
+    /// Events ingested, broken down by `event_type`.
+    events_total: CounterVec,
+    /// Number of events currently held in the store.
+    stored_events: Gauge,
+    /// Latency of handled HTTP requests, in seconds.
+    request_latency: Histogram,
+}
+
+impl Metrics {
+    fn new() -> Self {
+        let registry = Registry::new();
+        let events_total = CounterVec::new(
+            Opts::new("analytics_events_total", "Total analytics events ingested"),
+            &["event_type"],
+        )
+        .expect("valid counter opts");
+        let stored_events = Gauge::new(
+            "analytics_stored_events",
+            "Number of analytics events currently stored",
+        )
+        .expect("valid gauge opts");
+        let request_latency = Histogram::with_opts(HistogramOpts::new(
+            "analytics_request_duration_seconds",
+            "HTTP request latency in seconds",
+        ))
+        .expect("valid histogram opts");
+
+        registry
+            .register(Box::new(events_total.clone()))
+            .expect("register counter");
+        registry
+            .register(Box::new(stored_events.clone()))
+            .expect("register gauge");
+        registry
+            .register(Box::new(request_latency.clone()))
+            .expect("register histogram");
+
+        Self {
+            registry,
+            events_total,
+            stored_events,
+            request_latency,
+        }
+    }
+}
+
+/// Shared application state: the backing store plus a broadcast channel that
+/// live SSE subscribers listen on.
+struct AppStateInner {
+    store: Box<dyn EventStore>,
+    tx: broadcast::Sender<AnalyticsEvent>,
+    metrics: Metrics,
+}
+
+type AppState = Arc<AppStateInner>;
+
+/// Storage backend for analytics events. Every handler goes through this trait
+/// so behavior is identical regardless of which implementation is selected at
+/// startup.
+#[async_trait]
+trait EventStore: Send + Sync {
+    /// Persist a newly created event.
+    async fn insert(&self, event: AnalyticsEvent);
+
+    /// Fetch a single event by id, if present.
+    async fn get(&self, id: Uuid) -> Option<AnalyticsEvent>;
+
+    /// Return a newest-first page of events matching `params` together with the
+    /// total number of matches (pre-pagination) for the `x-total-count` header.
+    async fn query(&self, params: &EventsQuery) -> (Vec<AnalyticsEvent>, usize);
+
+    /// Aggregate counts and the ten most recent events.
+    async fn stats(&self) -> EventStats;
+}
+
+/// The original in-memory vector store, now behind [`EventStore`].
+#[derive(Default)]
+struct InMemoryStore {
+    events: RwLock<Vec<AnalyticsEvent>>,
+}
+
+#[async_trait]
+impl EventStore for InMemoryStore {
+    async fn insert(&self, event: AnalyticsEvent) {
+        self.events.write().await.push(event);
+    }
+
+    async fn get(&self, id: Uuid) -> Option<AnalyticsEvent> {
+        self.events.read().await.iter().find(|e| e.id == id).cloned()
+    }
+
+    async fn query(&self, params: &EventsQuery) -> (Vec<AnalyticsEvent>, usize) {
+        let events = self.events.read().await;
+
+        let mut matched: Vec<AnalyticsEvent> = events
+            .iter()
+            .filter(|e| {
+                params
+                    .event_type
+                    .as_ref()
+                    .is_none_or(|t| &e.event_type == t)
+            })
+            .filter(|e| {
+                params
+                    .user_id
+                    .as_ref()
+                    .is_none_or(|u| e.user_id.as_ref() == Some(u))
+            })
+            .filter(|e| params.since.is_none_or(|s| e.timestamp >= s))
+            .filter(|e| params.until.is_none_or(|u| e.timestamp <= u))
+            .cloned()
+            .collect();
+        matched.sort_by_key(|b| std::cmp::Reverse(b.timestamp));
+
+        let total = matched.len();
+        let page = matched
+            .into_iter()
+            .skip(params.offset())
+            .take(params.limit())
+            .collect();
+        (page, total)
+    }
+
+    async fn stats(&self) -> EventStats {
+        let events = self.events.read().await;
+
+        let total_events = events.len();
+        let mut events_by_type = HashMap::new();
+        for event in events.iter() {
+            *events_by_type.entry(event.event_type.clone()).or_insert(0) += 1;
+        }
+
+        let mut recent_events = events.clone();
+        recent_events.sort_by_key(|b| std::cmp::Reverse(b.timestamp));
+        recent_events.truncate(10);
+
+        EventStats {
+            total_events,
+            events_by_type,
+            recent_events,
+        }
+    }
+}
+
+/// A SQLite-backed store using `sqlx`. Events live in an `events` table and
+/// `stats` is pushed down into SQL so aggregation does not stream every row
+/// into the service.
+struct SqliteStore {
+    pool: sqlx::SqlitePool,
+}
+
+impl SqliteStore {
+    /// Connect to `url`, creating the `events` table if it does not yet exist.
+    async fn connect(url: &str) -> Result<Self, sqlx::Error> {
+        use std::str::FromStr;
+
+        // Create the database file on first run rather than failing.
+        let options = sqlx::sqlite::SqliteConnectOptions::from_str(url)?.create_if_missing(true);
+
+        // An in-memory database only exists for the lifetime of the connection
+        // that created it, so every pooled connection would otherwise see its
+        // own empty database. Pin the pool to a single connection so reads see
+        // what writes inserted.
+        let is_memory = url.contains(":memory:") || url.contains("mode=memory");
+        let mut pool_options =
+            sqlx::sqlite::SqlitePoolOptions::new().max_connections(if is_memory { 1 } else { 5 });
+        if is_memory {
+            // Never let the pool retire that connection, or the data goes with it.
+            pool_options = pool_options
+                .idle_timeout(None::<Duration>)
+                .max_lifetime(None::<Duration>);
+        }
+        let pool = pool_options
+            .connect_with(options)
+            .await?;
+        sqlx::query(
+            "CREATE TABLE IF NOT EXISTS events (
+                id TEXT PRIMARY KEY,
+                event_type TEXT NOT NULL,
+                user_id TEXT,
+                properties TEXT NOT NULL,
+                timestamp TEXT NOT NULL
+            )",
+        )
+        .execute(&pool)
+        .await?;
+        Ok(Self { pool })
+    }
+
+    /// Reconstruct an [`AnalyticsEvent`] from a queried row.
+    fn row_to_event(row: &sqlx::sqlite::SqliteRow) -> AnalyticsEvent {
+        use sqlx::Row;
+        let properties: String = row.get("properties");
+        let timestamp: String = row.get("timestamp");
+        AnalyticsEvent {
+            id: Uuid::parse_str(row.get("id")).unwrap_or_default(),
+            event_type: row.get("event_type"),
+            user_id: row.get("user_id"),
+            properties: serde_json::from_str(&properties).unwrap_or_default(),
+            timestamp: chrono::DateTime::parse_from_rfc3339(&timestamp)
+                .map(|t| t.with_timezone(&chrono::Utc))
+                .unwrap_or_else(|_| chrono::Utc::now()),
+        }
+    }
+}
+
+#[async_trait]
+impl EventStore for SqliteStore {
+    async fn insert(&self, event: AnalyticsEvent) {
+        let properties = serde_json::to_string(&event.properties).unwrap_or_else(|_| "{}".into());
+        if let Err(err) = sqlx::query(
+            "INSERT INTO events (id, event_type, user_id, properties, timestamp)
+             VALUES (?, ?, ?, ?, ?)",
+        )
+        .bind(event.id.to_string())
+        .bind(&event.event_type)
+        .bind(&event.user_id)
+        .bind(properties)
+        .bind(event.timestamp.to_rfc3339())
+        .execute(&self.pool)
+        .await
+        {
+            tracing::error!("failed to persist event: {err}");
+        }
+    }
+
+    async fn get(&self, id: Uuid) -> Option<AnalyticsEvent> {
+        sqlx::query("SELECT * FROM events WHERE id = ?")
+            .bind(id.to_string())
+            .fetch_optional(&self.pool)
+            .await
+            .ok()
+            .flatten()
+            .map(|row| Self::row_to_event(&row))
+    }
+
+    async fn query(&self, params: &EventsQuery) -> (Vec<AnalyticsEvent>, usize) {
+        let mut count = sqlx::QueryBuilder::new("SELECT COUNT(*) FROM events WHERE 1=1");
+        let mut select = sqlx::QueryBuilder::new("SELECT * FROM events WHERE 1=1");
+        for builder in [&mut count, &mut select] {
+            if let Some(ref t) = params.event_type {
+                builder.push(" AND event_type = ").push_bind(t.clone());
+            }
+            if let Some(ref u) = params.user_id {
+                builder.push(" AND user_id = ").push_bind(u.clone());
+            }
+            if let Some(s) = params.since {
+                builder.push(" AND timestamp >= ").push_bind(s.to_rfc3339());
+            }
+            if let Some(u) = params.until {
+                builder.push(" AND timestamp <= ").push_bind(u.to_rfc3339());
+            }
+        }
+
+        use sqlx::Row;
+        let total: i64 = count
+            .build()
+            .fetch_one(&self.pool)
+            .await
+            .map(|row| row.get(0))
+            .unwrap_or(0);
+
+        select.push(" ORDER BY timestamp DESC LIMIT ");
+        select.push_bind(params.limit() as i64);
+        select.push(" OFFSET ").push_bind(params.offset() as i64);
+
+        let page = select
+            .build()
+            .fetch_all(&self.pool)
+            .await
+            .map(|rows| rows.iter().map(Self::row_to_event).collect())
+            .unwrap_or_default();
+
+        (page, total as usize)
+    }
+
+    async fn stats(&self) -> EventStats {
+        use sqlx::Row;
+
+        let total_events: i64 = sqlx::query("SELECT COUNT(*) FROM events")
+            .fetch_one(&self.pool)
+            .await
+            .map(|row| row.get(0))
+            .unwrap_or(0);
+
+        let events_by_type = sqlx::query("SELECT event_type, COUNT(*) FROM events GROUP BY event_type")
+            .fetch_all(&self.pool)
+            .await
+            .map(|rows| {
+                rows.iter()
+                    .map(|row| (row.get::<String, _>(0), row.get::<i64, _>(1) as usize))
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        let recent_events = sqlx::query("SELECT * FROM events ORDER BY timestamp DESC LIMIT 10")
+            .fetch_all(&self.pool)
+            .await
+            .map(|rows| rows.iter().map(Self::row_to_event).collect())
+            .unwrap_or_default();
+
+        EventStats {
+            total_events: total_events as usize,
+            events_by_type,
+            recent_events,
+        }
+    }
+}
+
+type HmacSha256 = Hmac<Sha256>;
+
+/// Environment variable holding the shared secret used to verify the
+/// `X-Signature` header on incoming events. When it is unset, verification is
+/// skipped entirely so the service can be driven with unsigned requests.
+const WEBHOOK_SECRET_ENV: &str = "WEBHOOK_SECRET";
+
+/// A JSON body whose raw bytes have been authenticated with an
+/// `HMAC-SHA256` signature supplied in the `X-Signature` header
+/// (`sha256=<hex>`). The MAC is computed over the exact received bytes rather
+/// than a re-serialized struct so it survives any formatting differences.
+struct SignedJson<T>(T);
+
+#[async_trait]
+impl<T, S> FromRequest<S> for SignedJson<T>
+where
+    T: serde::de::DeserializeOwned,
+    S: Send + Sync,
+{
+    type Rejection = StatusCode;
+
+    async fn from_request(req: Request, state: &S) -> Result<Self, Self::Rejection> {
+        let headers = req.headers().clone();
+        let bytes = Bytes::from_request(req, state)
+            .await
+            .map_err(|_| StatusCode::BAD_REQUEST)?;
+
+        verify_signature(&headers, &bytes)?;
+
+        let value = serde_json::from_slice(&bytes).map_err(|_| StatusCode::BAD_REQUEST)?;
+        Ok(SignedJson(value))
+    }
+}
+
+/// Verify the HMAC-SHA256 signature of `body` against the `X-Signature` header.
+///
+/// Returns `Ok(())` when the secret env var is unset (verification disabled) or
+/// when the signature matches, and `401` otherwise.
+fn verify_signature(headers: &HeaderMap, body: &[u8]) -> Result<(), StatusCode> {
+    let secret = match std::env::var(WEBHOOK_SECRET_ENV) {
+        Ok(secret) => secret,
+        // Verification is a no-op only when the secret is explicitly unset.
+        Err(_) => return Ok(()),
+    };
+
+    let header = headers
+        .get("x-signature")
+        .and_then(|v| v.to_str().ok())
+        .ok_or(StatusCode::UNAUTHORIZED)?;
+
+    let hex_sig = header
+        .strip_prefix("sha256=")
+        .ok_or(StatusCode::UNAUTHORIZED)?;
+
+    // Reject empty signatures before doing any work.
+    if hex_sig.is_empty() {
+        return Err(StatusCode::UNAUTHORIZED);
+    }
+
+    let provided = hex::decode(hex_sig).map_err(|_| StatusCode::UNAUTHORIZED)?;
+
+    let mut mac =
+        HmacSha256::new_from_slice(secret.as_bytes()).map_err(|_| StatusCode::UNAUTHORIZED)?;
+    mac.update(body);
+
+    // Length check before the constant-time comparison.
+    let expected = mac.clone().finalize().into_bytes();
+    if provided.len() != expected.len() {
+        return Err(StatusCode::UNAUTHORIZED);
+    }
+
+    // `verify_slice` performs a constant-time comparison internally.
+    mac.verify_slice(&provided)
+        .map_err(|_| StatusCode::UNAUTHORIZED)
+}
+
+/// Environment variable holding the HS256 secret used to validate bearer
+/// tokens. Write access is only gated when this variable is present.
+const AUTH_SECRET_ENV: &str = "AUTH_SECRET";
+
+/// Claims carried by an authenticated request's JWT.
+#[derive(Debug, Serialize, Deserialize)]
+struct Claims {
+    /// Subject — the authenticated principal.
+    sub: String,
+    /// Expiry, as a Unix timestamp. Validated by `jsonwebtoken`.
+    #[allow(dead_code)]
+    exp: usize,
+}
+
+/// The authenticated principal for a request.
+///
+/// When `AUTH_SECRET` is unset the check is disabled and the principal is
+/// always anonymous (`None`); when it is set, extraction rejects missing,
+/// malformed, or expired tokens with `401`.
+#[derive(Debug)]
+struct AuthPrincipal(Option<String>);
+
+#[async_trait]
+impl<S> FromRequestParts<S> for AuthPrincipal
+where
+    S: Send + Sync,
+{
+    type Rejection = StatusCode;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        let secret = match std::env::var(AUTH_SECRET_ENV) {
+            Ok(secret) => secret,
+            // Auth disabled: everything is anonymous.
+            Err(_) => return Ok(AuthPrincipal(None)),
+        };
+
+        let token = parts
+            .headers
+            .get(axum::http::header::AUTHORIZATION)
+            .and_then(|v| v.to_str().ok())
+            .and_then(|v| v.strip_prefix("Bearer "))
+            .ok_or(StatusCode::UNAUTHORIZED)?;
+
+        let data = decode::<Claims>(
+            token,
+            &DecodingKey::from_secret(secret.as_bytes()),
+            &Validation::default(),
+        )
+        .map_err(|_| StatusCode::UNAUTHORIZED)?;
+
+        Ok(AuthPrincipal(Some(data.claims.sub)))
+    }
+}
 
 #[tokio::main]
 async fn main() {
@@ -44,16 +545,41 @@ async fn main() {
         .with_max_level(Level::INFO)
         .init();
 
-    // Initialize in-memory storage (in production, use a database)
-    let state: AppState = Arc::new(RwLock::new(Vec::new()));
+    // Select the storage backend. A `DATABASE_URL`/`STORAGE` value (other than
+    // the literal `memory`) connects the SQLite backend; otherwise events are
+    // kept in memory.
+    let store: Box<dyn EventStore> = match std::env::var("DATABASE_URL")
+        .ok()
+        .or_else(|| std::env::var("STORAGE").ok())
+    {
+        Some(url) if url != "memory" => Box::new(
+            SqliteStore::connect(&url)
+                .await
+                .expect("failed to open SQLite store"),
+        ),
+        _ => Box::new(InMemoryStore::default()),
+    };
+
+    let (tx, _rx) = broadcast::channel(EVENT_CHANNEL_CAPACITY);
+    let state: AppState = Arc::new(AppStateInner {
+        store,
+        tx,
+        metrics: Metrics::new(),
+    });
 
     // Build our application with routes
     let app = Router::new()
         .route("/health", get(health_check))
         .route("/events", post(create_event))
         .route("/events", get(get_events))
+        .route("/events/stream", get(stream_events))
         .route("/events/:id", get(get_event))
         .route("/stats", get(get_stats))
+        .route("/metrics", get(get_metrics))
+        .layer(axum::middleware::from_fn_with_state(
+            state.clone(),
+            track_latency,
+        ))
         .layer(
             CorsLayer::new()
                 .allow_origin(Any)
@@ -71,6 +597,34 @@ async fn main() {
     axum::serve(listener, app).await.unwrap();
 }
 
+/// Middleware that records the latency of each handled request in the
+/// `analytics_request_duration_seconds` histogram.
+async fn track_latency(
+    State(state): State<AppState>,
+    req: Request,
+    next: axum::middleware::Next,
+) -> axum::response::Response {
+    let timer = state.metrics.request_latency.start_timer();
+    let response = next.run(req).await;
+    timer.observe_duration();
+    response
+}
+
+async fn get_metrics(State(state): State<AppState>) -> Result<String, StatusCode> {
+    // Refresh the gauge from the store at scrape time so it reflects events
+    // actually stored (including across restarts and with the SQLite backend),
+    // not just inserts seen by this process.
+    let total = state.store.stats().await.total_events;
+    state.metrics.stored_events.set(total as f64);
+
+    let encoder = TextEncoder::new();
+    let mut buffer = Vec::new();
+    encoder
+        .encode(&state.metrics.registry.gather(), &mut buffer)
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    String::from_utf8(buffer).map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)
+}
+
 async fn health_check() -> Json<serde_json::Value> {
     Json(serde_json::json!({
         "status": "healthy",
@@ -80,58 +634,246 @@ async fn health_check() -> Json<serde_json::Value> {
 
 async fn create_event(
     State(state): State<AppState>,
-    Json(payload): Json<CreateEventRequest>,
+    AuthPrincipal(principal): AuthPrincipal,
+    SignedJson(payload): SignedJson<CreateEventRequest>,
 ) -> Result<Json<AnalyticsEvent>, StatusCode> {
     let event = AnalyticsEvent {
         id: Uuid::new_v4(),
         event_type: payload.event_type,
-        user_id: payload.user_id,
+        // Attribute the event to the authenticated principal when the payload
+        // does not specify a user id.
+        user_id: payload.user_id.or(principal),
         properties: payload.properties,
         timestamp: chrono::Utc::now(),
     };
 
-    let mut events = state.write().await;
-    events.push(event.clone());
-    
+    state.store.insert(event.clone()).await;
+    state
+        .metrics
+        .events_total
+        .with_label_values(&[&event.event_type])
+        .inc();
+
+    // Fan the event out to any live SSE subscribers. An error here just means
+    // there are no active receivers, which is not a failure for the writer.
+    let _ = state.tx.send(event.clone());
+
     info!("Created analytics event: {:?}", event);
     Ok(Json(event))
 }
 
-async fn get_events(State(state): State<AppState>) -> Json<Vec<AnalyticsEvent>> {
-    let events = state.read().await;
-    Json(events.clone())
+async fn get_events(
+    State(state): State<AppState>,
+    Query(params): Query<EventsQuery>,
+) -> impl axum::response::IntoResponse {
+    let (page, total) = state.store.query(&params).await;
+
+    let mut headers = HeaderMap::new();
+    headers.insert("x-total-count", total.into());
+
+    (headers, Json(page))
+}
+
+async fn stream_events(
+    State(state): State<AppState>,
+    Query(params): Query<StreamQuery>,
+) -> Sse<impl Stream<Item = Result<Event, Infallible>>> {
+    let filter = params.event_type;
+
+    let stream = BroadcastStream::new(state.tx.subscribe()).filter_map(move |result| {
+        let filter = filter.clone();
+        async move {
+            // Drop lagged-subscriber errors rather than terminating the stream.
+            let event = result.ok()?;
+            if let Some(ref wanted) = filter {
+                if &event.event_type != wanted {
+                    return None;
+                }
+            }
+            // Drop events that fail to serialize rather than panicking the task.
+            Event::default().json_data(&event).ok().map(Ok)
+        }
+    });
+
+    Sse::new(stream).keep_alive(KeepAlive::new().interval(Duration::from_secs(15)))
 }
 
 async fn get_event(
     State(state): State<AppState>,
     Path(id): Path<Uuid>,
 ) -> Result<Json<AnalyticsEvent>, StatusCode> {
-    let events = state.read().await;
-    
-    if let Some(event) = events.iter().find(|e| e.id == id) {
-        Ok(Json(event.clone()))
-    } else {
-        Err(StatusCode::NOT_FOUND)
+    match state.store.get(id).await {
+        Some(event) => Ok(Json(event)),
+        None => Err(StatusCode::NOT_FOUND),
     }
 }
 
 async fn get_stats(State(state): State<AppState>) -> Json<EventStats> {
-    let events = state.read().await;
-    
-    let total_events = events.len();
-    let mut events_by_type = HashMap::new();
-    
-    for event in events.iter() {
-        *events_by_type.entry(event.event_type.clone()).or_insert(0) += 1;
-    }
-    
-    let mut recent_events = events.clone();
-    recent_events.sort_by(|a, b| b.timestamp.cmp(&a.timestamp));
-    recent_events.truncate(10);
-    
-    Json(EventStats {
-        total_events,
-        events_by_type,
-        recent_events,
-    })
+    Json(state.store.stats().await)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn signed_header(secret: &[u8], body: &[u8]) -> String {
+        let mut mac = HmacSha256::new_from_slice(secret).unwrap();
+        mac.update(body);
+        format!("sha256={}", hex::encode(mac.finalize().into_bytes()))
+    }
+
+    fn header_map(signature: &str) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        headers.insert("x-signature", signature.parse().unwrap());
+        headers
+    }
+
+    #[test]
+    fn hmac_verification_accepts_and_rejects() {
+        let body = br#"{"event_type":"click"}"#;
+        std::env::set_var(WEBHOOK_SECRET_ENV, "topsecret");
+
+        // A correct signature passes.
+        let headers = header_map(&signed_header(b"topsecret", body));
+        assert!(verify_signature(&headers, body).is_ok());
+
+        // A missing header is rejected.
+        assert_eq!(
+            verify_signature(&HeaderMap::new(), body).unwrap_err(),
+            StatusCode::UNAUTHORIZED
+        );
+
+        // An empty signature is rejected.
+        assert_eq!(
+            verify_signature(&header_map("sha256="), body).unwrap_err(),
+            StatusCode::UNAUTHORIZED
+        );
+
+        // A signature of the wrong length is rejected.
+        assert_eq!(
+            verify_signature(&header_map("sha256=abcd"), body).unwrap_err(),
+            StatusCode::UNAUTHORIZED
+        );
+
+        // A well-formed but wrong signature is rejected.
+        let wrong = format!("sha256={}", hex::encode([0u8; 32]));
+        assert_eq!(
+            verify_signature(&header_map(&wrong), body).unwrap_err(),
+            StatusCode::UNAUTHORIZED
+        );
+
+        // Verification is disabled when the secret is unset.
+        std::env::remove_var(WEBHOOK_SECRET_ENV);
+        assert!(verify_signature(&HeaderMap::new(), body).is_ok());
+    }
+
+    fn parts_with_auth(auth: Option<&str>) -> Parts {
+        let mut builder = axum::http::Request::builder();
+        if let Some(auth) = auth {
+            builder = builder.header(axum::http::header::AUTHORIZATION, auth);
+        }
+        builder
+            .body(axum::body::Body::empty())
+            .unwrap()
+            .into_parts()
+            .0
+    }
+
+    fn mint_token(sub: &str, exp: usize) -> String {
+        use jsonwebtoken::{encode, EncodingKey, Header};
+        let claims = Claims {
+            sub: sub.to_string(),
+            exp,
+        };
+        encode(
+            &Header::default(),
+            &claims,
+            &EncodingKey::from_secret(b"jwtsecret"),
+        )
+        .unwrap()
+    }
+
+    #[tokio::test]
+    async fn jwt_extraction_accepts_and_rejects() {
+        std::env::set_var(AUTH_SECRET_ENV, "jwtsecret");
+
+        // A valid token surfaces its subject as the principal.
+        let token = mint_token("alice", 99_999_999_999);
+        let mut parts = parts_with_auth(Some(&format!("Bearer {token}")));
+        let principal = AuthPrincipal::from_request_parts(&mut parts, &())
+            .await
+            .unwrap();
+        assert_eq!(principal.0.as_deref(), Some("alice"));
+
+        // An expired token is rejected.
+        let token = mint_token("bob", 1);
+        let mut parts = parts_with_auth(Some(&format!("Bearer {token}")));
+        assert_eq!(
+            AuthPrincipal::from_request_parts(&mut parts, &())
+                .await
+                .unwrap_err(),
+            StatusCode::UNAUTHORIZED
+        );
+
+        // A malformed token is rejected.
+        let mut parts = parts_with_auth(Some("Bearer not.a.jwt"));
+        assert_eq!(
+            AuthPrincipal::from_request_parts(&mut parts, &())
+                .await
+                .unwrap_err(),
+            StatusCode::UNAUTHORIZED
+        );
+
+        // A missing Authorization header is rejected.
+        let mut parts = parts_with_auth(None);
+        assert_eq!(
+            AuthPrincipal::from_request_parts(&mut parts, &())
+                .await
+                .unwrap_err(),
+            StatusCode::UNAUTHORIZED
+        );
+
+        std::env::remove_var(AUTH_SECRET_ENV);
+    }
+
+    fn test_state() -> AppState {
+        let (tx, _rx) = broadcast::channel(EVENT_CHANNEL_CAPACITY);
+        Arc::new(AppStateInner {
+            store: Box::new(InMemoryStore::default()),
+            tx,
+            metrics: Metrics::new(),
+        })
+    }
+
+    fn event_request(user_id: Option<&str>) -> CreateEventRequest {
+        CreateEventRequest {
+            event_type: "click".to_string(),
+            user_id: user_id.map(str::to_string),
+            properties: HashMap::new(),
+        }
+    }
+
+    #[tokio::test]
+    async fn authenticated_subject_attributed_when_user_id_absent() {
+        // With no user_id in the payload, the event is attributed to the
+        // authenticated principal.
+        let created = create_event(
+            State(test_state()),
+            AuthPrincipal(Some("alice".to_string())),
+            SignedJson(event_request(None)),
+        )
+        .await
+        .unwrap();
+        assert_eq!(created.user_id.as_deref(), Some("alice"));
+
+        // An explicit payload user_id wins over the principal.
+        let created = create_event(
+            State(test_state()),
+            AuthPrincipal(Some("alice".to_string())),
+            SignedJson(event_request(Some("bob"))),
+        )
+        .await
+        .unwrap();
+        assert_eq!(created.user_id.as_deref(), Some("bob"));
+    }
 }
\ No newline at end of file